@@ -132,6 +132,19 @@ use lrlex::CTLexerBuilder;
         !! flatten, map_err, and Lexeme can be used together to create a powerful error handling system. !!
 
 
+        --- Start conditions ---
+
+        `calc.l` declares two exclusive start conditions, `%x COMMENT` and `%x STRING`, mirroring the
+        classic Lex `%S name` / `<y>x` syntax described in main.rs. A rule of the form `<INITIAL>\" STRING`
+        switches the lexer into the `STRING` state without emitting a token (the opening quote is just a
+        delimiter); rules prefixed `<STRING>` are then the only ones active until a rule switches back to
+        `INITIAL` (on the closing quote). `COMMENT` works the same way, switching on `#` and back to
+        `INITIAL` on the next newline, with every character in between matched and discarded. CTLexerBuilder
+        compiles these the same way it does the unconditional rules - it reads the whole `.l` file in
+        `lexer_in_src_dir("calc.l")` below and generates the state machine, so no extra wiring is needed in
+        this file for start conditions to work.
+
+
         */
 
 fn main() {
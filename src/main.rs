@@ -1,7 +1,9 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::io::{self, BufRead, Write};
 
-use lrlex::lrlex_mod;
-use lrpar::lrpar_mod;
+use lrlex::{lrlex_mod, DefaultLexerTypes, LRNonStreamingLexerDef, Lexer};
+use lrpar::{lrpar_mod, LexParseError, Lexeme, ParseRepair};
 
 // Using `lrlex_mod!` brings the lexer for `calc.l` into scope. By default the
 // module name will be `calc_l` (i.e. the file name, minus any extensions,
@@ -209,7 +211,34 @@ fn main() {
     // Get the `LexerDef` for the `calc` language.
     let lexerdef = calc_l::lexerdef();
     // calc_l is the module name of the lexer file generated by lrlex_mod! macro and build.rs file
+    // The variable environment is created once, for the whole run, so that
+    // assignments made in one statement (`x = 2 + 3`) are still visible in
+    // later ones (`x * 4`). `calc_y::parse` takes it as a `%parse-param`,
+    // which must be `Copy` - a shared reference to a `RefCell` fits the bill.
+    let env: RefCell<HashMap<String, u64>> = RefCell::new(HashMap::new());
+
+    // A file path argument means "read the whole file and parse it as a
+    // single `;`-separated Program"; with no argument we fall back to the
+    // interactive, line-at-a-time REPL. Either way a submitted chunk of
+    // input can itself contain several `;`-separated statements.
+    if let Some(path) = std::env::args().nth(1) {
+        let buf = match std::fs::read_to_string(&path) {
+            Ok(buf) => buf,
+            Err(e) => {
+                eprintln!("couldn't read {}: {}", path, e);
+                std::process::exit(1);
+            }
+        };
+        process(&lexerdef, &env, &buf, false);
+        return;
+    }
+
     let stdin = io::stdin();
+    // `:tree` toggles between printing the evaluated result of each
+    // statement (the default) and printing its parsed `Expr` AST, so
+    // learners can see how an expression associated before it gets reduced
+    // to a number.
+    let mut tree_mode = false;
     loop {
         print!(">>> ");
         // ask the user to input an expression
@@ -219,20 +248,176 @@ fn main() {
                 if l.trim().is_empty() {
                     continue;
                 }
-                // Now we create a lexer with the `lexer` method with which
-                // we can lex an input.
-                let lexer = lexerdef.lexer(l);
-                // Pass the lexer to the parser and lex and parse the input.
-                let (res, errs) = calc_y::parse(&lexer);
-                for e in errs {
-                    println!("{}", e.pp(&lexer, &calc_y::token_epp));
-                }
-                match res {
-                    Some(Ok(r)) => println!("Result: {:?}", r),
-                    _ => eprintln!("Unable to evaluate expression.")
+                if l.trim() == ":tree" {
+                    tree_mode = !tree_mode;
+                    println!("tree mode: {}", if tree_mode { "on" } else { "off" });
+                    continue;
                 }
+                process(&lexerdef, &env, l, tree_mode);
             }
             _ => break
         }
     }
+}
+
+/// Lexes and parses `input` as a single `Program` (one or more `;`-separated
+/// statements) and either prints each statement's parsed tree (`tree_mode`)
+/// or evaluates and prints each statement's result, in order.
+fn process(
+    lexerdef: &LRNonStreamingLexerDef<DefaultLexerTypes>,
+    env: &RefCell<HashMap<String, u64>>,
+    input: &str,
+    tree_mode: bool,
+) {
+    // Now we create a lexer with the `lexer` method with which
+    // we can lex an input.
+    let lexer = lexerdef.lexer(input);
+    // Pass the lexer to the parser and lex and parse the input.
+    let (res, errs) = calc_y::parse(&lexer, env);
+    for e in &errs {
+        println!("{}", e.pp(&lexer, &calc_y::token_epp));
+    }
+    // For the (common) single-error, single-repair case, show the
+    // concrete repair lrpar picked and what evaluating the repaired input
+    // would have produced, clearly marked as a guess - the repair is
+    // lrpar's best guess at what the user meant, not what they actually
+    // wrote.
+    if let [LexParseError::ParseError(pe)] = errs.as_slice() {
+        let repairs = pe.repairs();
+        if let [seq] = repairs.as_slice() {
+            println!("  repair: {}", describe_repairs(seq, &lexer));
+            let guessed = guess_corrected_input(input, &lexer, pe, seq);
+            let guess_lexer = lexerdef.lexer(&guessed);
+            // Evaluate against a throwaway copy of `env`, never the real one -
+            // this is only a guess at what the user meant, and it must not
+            // leave a phantom assignment behind for later statements to see.
+            let scratch_env = RefCell::new(env.borrow().clone());
+            if let (Some(Ok(stmts)), _) = calc_y::parse(&guess_lexer, &scratch_env) {
+                let results: Vec<String> = stmts
+                    .iter()
+                    .map(|stmt| match stmt.eval(&scratch_env, &guessed) {
+                        Ok(r) => format!("{}", r),
+                        Err(e) => format!("error: {}", e)
+                    })
+                    .collect();
+                println!(
+                    "  as if you had written {:?}: Result: {} (guess)",
+                    guessed,
+                    results.join(", ")
+                );
+            }
+        } else {
+            for (i, seq) in repairs.iter().enumerate() {
+                println!("  repair {}: {}", i + 1, describe_repairs(seq, &lexer));
+            }
+        }
+    }
+    match res {
+        Some(Ok(stmts)) => {
+            for stmt in &stmts {
+                if tree_mode {
+                    print_tree(stmt, 0);
+                } else {
+                    match stmt.eval(env, input) {
+                        Ok(r) => println!("Result: {}", r),
+                        Err(e) => eprintln!("{}", e)
+                    }
+                }
+            }
+        }
+        _ => eprintln!("Unable to evaluate expression.")
+    }
+}
+
+/// Describes a single repair sequence (one alternative lrpar considered) as
+/// a comma-separated list of its inserted/deleted/shifted lexemes.
+fn describe_repairs(seq: &[ParseRepair<u32>], lexer: &impl Lexer<DefaultLexerTypes>) -> String {
+    seq.iter()
+        .map(|r| match r {
+            ParseRepair::Insert(tidx) => format!("insert {}", calc_y::token_epp(*tidx).unwrap_or("?")),
+            ParseRepair::Delete(lexeme) => format!("delete {:?}", lexer.span_str(lexeme.span())),
+            ParseRepair::Shift(lexeme) => format!("shift {:?}", lexer.span_str(lexeme.span())),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Reconstructs what the input would have looked like had the user typed it
+/// so that `seq` was never needed: text up to the error point is kept
+/// verbatim, `seq`'s edits are applied in place, and input after the last
+/// touched lexeme is kept verbatim. This is a guess, not a guarantee -
+/// lrpar only guarantees the repaired *token stream* parses, not that this
+/// textual rendering round-trips through the lexer identically.
+fn guess_corrected_input(
+    original: &str,
+    lexer: &impl Lexer<DefaultLexerTypes>,
+    pe: &lrpar::ParseError<u32, DefaultLexerTypes>,
+    seq: &[ParseRepair<u32>],
+) -> String {
+    let error_start = pe.lexeme().span().start();
+    let mut last_end = error_start;
+    let mut edited = String::new();
+    for r in seq {
+        match r {
+            ParseRepair::Insert(tidx) => {
+                edited.push_str(guess_text_for_token(calc_y::token_epp(*tidx).unwrap_or("")));
+                edited.push(' ');
+            }
+            ParseRepair::Delete(lexeme) => last_end = lexeme.span().end(),
+            ParseRepair::Shift(lexeme) => {
+                edited.push_str(lexer.span_str(lexeme.span()));
+                edited.push(' ');
+                last_end = lexeme.span().end();
+            }
+        }
+    }
+    format!(
+        "{}{}{}",
+        &original[..error_start],
+        edited.trim_end(),
+        &original[last_end..]
+    )
+}
+
+/// Stand-in text for a lexeme that lrpar inserted during error recovery -
+/// there is no real user input to echo, so we show something of the right
+/// shape (`0` for a missing `INT`, `x` for a missing `ID`) instead.
+fn guess_text_for_token(name: &str) -> &'static str {
+    match name {
+        "INT" => "0",
+        "ID" => "x",
+        "+" => "+",
+        "*" => "*",
+        "(" => "(",
+        ")" => ")",
+        "=" => "=",
+        "STRING" => "\"\"",
+        ";" => ";",
+        _ => "?",
+    }
+}
+
+/// Pretty-prints an `Expr` AST, indenting one level per nesting level so that
+/// operator precedence and associativity are visible at a glance.
+fn print_tree(e: &calc_y::Expr, indent: usize) {
+    let pad = "  ".repeat(indent);
+    match e {
+        calc_y::Expr::Assign(id, rhs, _) => {
+            println!("{}Assign {}", pad, id);
+            print_tree(rhs, indent + 1);
+        }
+        calc_y::Expr::Add(l, r, _) => {
+            println!("{}Add", pad);
+            print_tree(l, indent + 1);
+            print_tree(r, indent + 1);
+        }
+        calc_y::Expr::Mul(l, r, _) => {
+            println!("{}Mul", pad);
+            print_tree(l, indent + 1);
+            print_tree(r, indent + 1);
+        }
+        calc_y::Expr::Num(v, _) => println!("{}Num({})", pad, v),
+        calc_y::Expr::Var(id, _) => println!("{}Var({})", pad, id),
+        calc_y::Expr::Str(s, _) => println!("{}Str({:?})", pad, s),
+    }
 }
\ No newline at end of file